@@ -0,0 +1,288 @@
+use std::fmt;
+
+/// The DNS header is always exactly 12 bytes, regardless of transport.
+const HEADER_SIZE: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    Query,
+    IQuery,
+    Status,
+    Reserved(u8),
+}
+
+impl OpCode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => OpCode::Query,
+            1 => OpCode::IQuery,
+            2 => OpCode::Status,
+            other => OpCode::Reserved(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryType {
+    A,
+    Ns,
+    Cname,
+    Soa,
+    Aaaa,
+    Other(u16),
+}
+
+impl QueryType {
+    fn from_u16(value: u16) -> Self {
+        match value {
+            1 => QueryType::A,
+            2 => QueryType::Ns,
+            5 => QueryType::Cname,
+            6 => QueryType::Soa,
+            28 => QueryType::Aaaa,
+            other => QueryType::Other(other),
+        }
+    }
+}
+
+// main only logs these fields via the `Debug` derive for now; response
+// building in the next chapter reads them directly.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct DnsHeader {
+    pub id: u16,
+    pub qr: bool,
+    pub opcode: OpCode,
+    pub aa: bool,
+    pub tc: bool,
+    pub rd: bool,
+    pub ra: bool,
+    pub rcode: u8,
+    pub qdcount: u16,
+    pub ancount: u16,
+    pub nscount: u16,
+    pub arcount: u16,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub qname: String,
+    pub qtype: QueryType,
+    pub qclass: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsMessage {
+    pub header: DnsHeader,
+    pub questions: Vec<Question>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The buffer is shorter than a DNS header.
+    PacketTooShort,
+    /// We ran off the end of the buffer while decoding a name or a fixed field.
+    UnexpectedEof,
+    /// A compression pointer pointed at or past its own position.
+    PointerLoop,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::PacketTooShort => write!(f, "packet is shorter than a DNS header"),
+            ParseError::UnexpectedEof => write!(f, "ran off the end of the packet while parsing"),
+            ParseError::PointerLoop => write!(f, "name compression pointer does not point backwards"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(buf: &[u8]) -> Result<DnsMessage, ParseError> {
+    if buf.len() < HEADER_SIZE {
+        return Err(ParseError::PacketTooShort);
+    }
+
+    let header = parse_header(buf);
+
+    let mut pos = HEADER_SIZE;
+    // `header.qdcount` is an unvalidated 16-bit field from the wire; pre-allocating
+    // from it directly would let a single short datagram claiming qdcount = 0xFFFF
+    // force a large allocation that's immediately discarded on the first parse error.
+    let mut questions = Vec::new();
+    for _ in 0..header.qdcount {
+        let (qname, name_len) = read_name(buf, pos)?;
+        pos += name_len;
+
+        let qtype = QueryType::from_u16(read_u16(buf, pos)?);
+        pos += 2;
+        let qclass = read_u16(buf, pos)?;
+        pos += 2;
+
+        questions.push(Question { qname, qtype, qclass });
+    }
+
+    Ok(DnsMessage { header, questions })
+}
+
+fn parse_header(buf: &[u8]) -> DnsHeader {
+    let id = u16::from_be_bytes([buf[0], buf[1]]);
+    let flags_hi = buf[2];
+    let flags_lo = buf[3];
+
+    DnsHeader {
+        id,
+        qr: flags_hi & 0b1000_0000 != 0,
+        opcode: OpCode::from_u8((flags_hi >> 3) & 0b0000_1111),
+        aa: flags_hi & 0b0000_0100 != 0,
+        tc: flags_hi & 0b0000_0010 != 0,
+        rd: flags_hi & 0b0000_0001 != 0,
+        ra: flags_lo & 0b1000_0000 != 0,
+        rcode: flags_lo & 0b0000_1111,
+        qdcount: u16::from_be_bytes([buf[4], buf[5]]),
+        ancount: u16::from_be_bytes([buf[6], buf[7]]),
+        nscount: u16::from_be_bytes([buf[8], buf[9]]),
+        arcount: u16::from_be_bytes([buf[10], buf[11]]),
+    }
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16, ParseError> {
+    let bytes = buf.get(pos..pos + 2).ok_or(ParseError::UnexpectedEof)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// Decodes a (possibly compressed) name starting at `start`, returning the
+/// decoded dotted name and the number of bytes consumed from `start` in the
+/// *original* message (i.e. not counting any bytes read after following a
+/// compression pointer).
+fn read_name(buf: &[u8], start: usize) -> Result<(String, usize), ParseError> {
+    let mut labels = Vec::new();
+    let mut pos = start;
+    let mut consumed_to: Option<usize> = None;
+
+    loop {
+        let length_byte = *buf.get(pos).ok_or(ParseError::UnexpectedEof)?;
+
+        if length_byte & 0xC0 == 0xC0 {
+            let lo = *buf.get(pos + 1).ok_or(ParseError::UnexpectedEof)?;
+            let pointer = (((length_byte & 0x3F) as usize) << 8) | lo as usize;
+
+            // A pointer must point strictly backwards, otherwise we'd either
+            // loop forever or read into the part of the message we haven't
+            // parsed yet.
+            if pointer >= pos {
+                return Err(ParseError::PointerLoop);
+            }
+
+            if consumed_to.is_none() {
+                consumed_to = Some(pos + 2);
+            }
+            pos = pointer;
+            continue;
+        }
+
+        if length_byte == 0 {
+            if consumed_to.is_none() {
+                consumed_to = Some(pos + 1);
+            }
+            break;
+        }
+
+        let label_len = length_byte as usize;
+        pos += 1;
+        let label_bytes = buf.get(pos..pos + label_len).ok_or(ParseError::UnexpectedEof)?;
+        labels.push(String::from_utf8_lossy(label_bytes).into_owned());
+        pos += label_len;
+    }
+
+    Ok((labels.join("."), consumed_to.unwrap() - start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_bytes(qdcount: u16) -> Vec<u8> {
+        let mut buf = vec![0u8; HEADER_SIZE];
+        buf[4..6].copy_from_slice(&qdcount.to_be_bytes());
+        buf
+    }
+
+    fn push_question(buf: &mut Vec<u8>, name_bytes: &[u8]) {
+        buf.extend_from_slice(name_bytes);
+        buf.extend_from_slice(&1u16.to_be_bytes()); // QTYPE A
+        buf.extend_from_slice(&1u16.to_be_bytes()); // QCLASS IN
+    }
+
+    #[test]
+    fn parses_a_question_with_no_compression() {
+        let mut buf = header_bytes(1);
+        push_question(&mut buf, &[1, b'a', 3, b'c', b'o', b'm', 0]);
+
+        let message = parse(&buf).expect("should parse");
+        assert_eq!(message.questions.len(), 1);
+        assert_eq!(message.questions[0].qname, "a.com");
+        assert_eq!(message.questions[0].qtype, QueryType::A);
+    }
+
+    #[test]
+    fn follows_a_compression_pointer() {
+        let mut buf = header_bytes(2);
+
+        let first_name_start = buf.len();
+        push_question(&mut buf, &[1, b'a', 3, b'c', b'o', b'm', 0]);
+
+        // Second question: the label "b" followed by a pointer back to the
+        // "com" label of the first question's name.
+        let com_label_offset = (first_name_start + 2) as u16;
+        let mut second_name = vec![1, b'b'];
+        second_name.extend_from_slice(&(0xC000 | com_label_offset).to_be_bytes());
+        push_question(&mut buf, &second_name);
+
+        let message = parse(&buf).expect("should parse");
+        assert_eq!(message.questions[1].qname, "b.com");
+    }
+
+    #[test]
+    fn follows_a_multi_hop_compression_chain() {
+        let mut buf = header_bytes(3);
+
+        let first_name_start = buf.len();
+        push_question(&mut buf, &[1, b'a', 3, b'c', b'o', b'm', 0]);
+
+        let com_label_offset = (first_name_start + 2) as u16;
+        let second_name_start = buf.len();
+        let mut second_name = vec![1, b'b'];
+        second_name.extend_from_slice(&(0xC000 | com_label_offset).to_be_bytes());
+        push_question(&mut buf, &second_name);
+
+        // Third question: "x" + a pointer into the second question's name,
+        // which itself ends in a pointer -- a two-hop backward chain.
+        let mut third_name = vec![1, b'x'];
+        third_name.extend_from_slice(&(0xC000 | second_name_start as u16).to_be_bytes());
+        push_question(&mut buf, &third_name);
+
+        let message = parse(&buf).expect("should parse");
+        assert_eq!(message.questions[2].qname, "x.b.com");
+    }
+
+    #[test]
+    fn rejects_a_pointer_that_points_at_itself() {
+        let mut buf = header_bytes(1);
+        let name_start = buf.len() as u16;
+        push_question(&mut buf, &(0xC000 | name_start).to_be_bytes());
+
+        assert_eq!(parse(&buf).unwrap_err(), ParseError::PointerLoop);
+    }
+
+    #[test]
+    fn rejects_a_pointer_that_points_forward() {
+        let mut buf = header_bytes(1);
+        let name_start = buf.len() as u16;
+        push_question(&mut buf, &(0xC000 | (name_start + 10)).to_be_bytes());
+
+        assert_eq!(parse(&buf).unwrap_err(), ParseError::PointerLoop);
+    }
+}
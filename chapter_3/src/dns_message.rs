@@ -0,0 +1,383 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// The DNS header is always exactly 12 bytes, regardless of transport.
+const HEADER_SIZE: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    Query,
+    IQuery,
+    Status,
+    Reserved(u8),
+}
+
+impl OpCode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => OpCode::Query,
+            1 => OpCode::IQuery,
+            2 => OpCode::Status,
+            other => OpCode::Reserved(other),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            OpCode::Query => 0,
+            OpCode::IQuery => 1,
+            OpCode::Status => 2,
+            OpCode::Reserved(other) => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryType {
+    A,
+    Ns,
+    Cname,
+    Soa,
+    Aaaa,
+    Other(u16),
+}
+
+impl QueryType {
+    fn from_u16(value: u16) -> Self {
+        match value {
+            1 => QueryType::A,
+            2 => QueryType::Ns,
+            5 => QueryType::Cname,
+            6 => QueryType::Soa,
+            28 => QueryType::Aaaa,
+            other => QueryType::Other(other),
+        }
+    }
+
+    pub fn to_u16(self) -> u16 {
+        match self {
+            QueryType::A => 1,
+            QueryType::Ns => 2,
+            QueryType::Cname => 5,
+            QueryType::Soa => 6,
+            QueryType::Aaaa => 28,
+            QueryType::Other(other) => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DnsHeader {
+    pub id: u16,
+    pub qr: bool,
+    pub opcode: OpCode,
+    pub aa: bool,
+    pub tc: bool,
+    pub rd: bool,
+    pub ra: bool,
+    pub rcode: u8,
+    pub qdcount: u16,
+    pub ancount: u16,
+    pub nscount: u16,
+    pub arcount: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub qname: String,
+    pub qtype: QueryType,
+    pub qclass: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordData {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+}
+
+impl RecordData {
+    fn rdata_bytes(&self) -> Vec<u8> {
+        match self {
+            RecordData::A(addr) => addr.octets().to_vec(),
+            RecordData::Aaaa(addr) => addr.octets().to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub rtype: QueryType,
+    pub rclass: u16,
+    pub ttl: u32,
+    pub data: RecordData,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsMessage {
+    pub header: DnsHeader,
+    pub questions: Vec<Question>,
+    pub answers: Vec<Record>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The buffer is shorter than a DNS header.
+    PacketTooShort,
+    /// We ran off the end of the buffer while decoding a name or a fixed field.
+    UnexpectedEof,
+    /// A compression pointer pointed at or past its own position.
+    PointerLoop,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::PacketTooShort => write!(f, "packet is shorter than a DNS header"),
+            ParseError::UnexpectedEof => write!(f, "ran off the end of the packet while parsing"),
+            ParseError::PointerLoop => write!(f, "name compression pointer does not point backwards"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(buf: &[u8]) -> Result<DnsMessage, ParseError> {
+    if buf.len() < HEADER_SIZE {
+        return Err(ParseError::PacketTooShort);
+    }
+
+    let header = parse_header(buf);
+
+    let mut pos = HEADER_SIZE;
+    // `header.qdcount` is an unvalidated 16-bit field from the wire; pre-allocating
+    // from it directly would let a single short datagram claiming qdcount = 0xFFFF
+    // force a large allocation that's immediately discarded on the first parse error.
+    let mut questions = Vec::new();
+    for _ in 0..header.qdcount {
+        let (qname, name_len) = read_name(buf, pos)?;
+        pos += name_len;
+
+        let qtype = QueryType::from_u16(read_u16(buf, pos)?);
+        pos += 2;
+        let qclass = read_u16(buf, pos)?;
+        pos += 2;
+
+        questions.push(Question { qname, qtype, qclass });
+    }
+
+    Ok(DnsMessage { header, questions, answers: Vec::new() })
+}
+
+fn parse_header(buf: &[u8]) -> DnsHeader {
+    let id = u16::from_be_bytes([buf[0], buf[1]]);
+    let flags_hi = buf[2];
+    let flags_lo = buf[3];
+
+    DnsHeader {
+        id,
+        qr: flags_hi & 0b1000_0000 != 0,
+        opcode: OpCode::from_u8((flags_hi >> 3) & 0b0000_1111),
+        aa: flags_hi & 0b0000_0100 != 0,
+        tc: flags_hi & 0b0000_0010 != 0,
+        rd: flags_hi & 0b0000_0001 != 0,
+        ra: flags_lo & 0b1000_0000 != 0,
+        rcode: flags_lo & 0b0000_1111,
+        qdcount: u16::from_be_bytes([buf[4], buf[5]]),
+        ancount: u16::from_be_bytes([buf[6], buf[7]]),
+        nscount: u16::from_be_bytes([buf[8], buf[9]]),
+        arcount: u16::from_be_bytes([buf[10], buf[11]]),
+    }
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16, ParseError> {
+    let bytes = buf.get(pos..pos + 2).ok_or(ParseError::UnexpectedEof)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// Decodes a (possibly compressed) name starting at `start`, returning the
+/// decoded dotted name and the number of bytes consumed from `start` in the
+/// *original* message (i.e. not counting any bytes read after following a
+/// compression pointer).
+fn read_name(buf: &[u8], start: usize) -> Result<(String, usize), ParseError> {
+    let mut labels = Vec::new();
+    let mut pos = start;
+    let mut consumed_to: Option<usize> = None;
+
+    loop {
+        let length_byte = *buf.get(pos).ok_or(ParseError::UnexpectedEof)?;
+
+        if length_byte & 0xC0 == 0xC0 {
+            let lo = *buf.get(pos + 1).ok_or(ParseError::UnexpectedEof)?;
+            let pointer = (((length_byte & 0x3F) as usize) << 8) | lo as usize;
+
+            // A pointer must point strictly backwards, otherwise we'd either
+            // loop forever or read into the part of the message we haven't
+            // parsed yet.
+            if pointer >= pos {
+                return Err(ParseError::PointerLoop);
+            }
+
+            if consumed_to.is_none() {
+                consumed_to = Some(pos + 2);
+            }
+            pos = pointer;
+            continue;
+        }
+
+        if length_byte == 0 {
+            if consumed_to.is_none() {
+                consumed_to = Some(pos + 1);
+            }
+            break;
+        }
+
+        let label_len = length_byte as usize;
+        pos += 1;
+        let label_bytes = buf.get(pos..pos + label_len).ok_or(ParseError::UnexpectedEof)?;
+        labels.push(String::from_utf8_lossy(label_bytes).into_owned());
+        pos += label_len;
+    }
+
+    Ok((labels.join("."), consumed_to.unwrap() - start))
+}
+
+/// Encodes `name` as a sequence of length-prefixed labels terminated by a
+/// zero byte. We never emit compression pointers of our own; keeping
+/// serialization simple is worth a few extra bytes on the wire.
+fn encode_name(name: &str, out: &mut Vec<u8>) {
+    if !name.is_empty() {
+        for label in name.split('.') {
+            out.push(label.len() as u8);
+            out.extend_from_slice(label.as_bytes());
+        }
+    }
+    out.push(0);
+}
+
+/// Serializes `message` back into wire format.
+pub fn serialize(message: &DnsMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    out.extend_from_slice(&message.header.id.to_be_bytes());
+
+    let flags_hi = (message.header.qr as u8) << 7
+        | message.header.opcode.to_u8() << 3
+        | (message.header.aa as u8) << 2
+        | (message.header.tc as u8) << 1
+        | message.header.rd as u8;
+    let flags_lo = (message.header.ra as u8) << 7 | (message.header.rcode & 0b0000_1111);
+    out.push(flags_hi);
+    out.push(flags_lo);
+
+    out.extend_from_slice(&message.header.qdcount.to_be_bytes());
+    out.extend_from_slice(&message.header.ancount.to_be_bytes());
+    out.extend_from_slice(&message.header.nscount.to_be_bytes());
+    out.extend_from_slice(&message.header.arcount.to_be_bytes());
+
+    for question in &message.questions {
+        encode_name(&question.qname, &mut out);
+        out.extend_from_slice(&question.qtype.to_u16().to_be_bytes());
+        out.extend_from_slice(&question.qclass.to_be_bytes());
+    }
+
+    for record in &message.answers {
+        encode_name(&record.name, &mut out);
+        out.extend_from_slice(&record.rtype.to_u16().to_be_bytes());
+        out.extend_from_slice(&record.rclass.to_be_bytes());
+        out.extend_from_slice(&record.ttl.to_be_bytes());
+
+        let rdata = record.data.rdata_bytes();
+        out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        out.extend_from_slice(&rdata);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_a_response_with_an_a_record_answer() {
+        let message = DnsMessage {
+            header: DnsHeader {
+                id: 0xABCD,
+                qr: true,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 1,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![Question { qname: "a.com".to_string(), qtype: QueryType::A, qclass: 1 }],
+            answers: vec![Record {
+                name: "a.com".to_string(),
+                rtype: QueryType::A,
+                rclass: 1,
+                ttl: 300,
+                data: RecordData::A(Ipv4Addr::new(127, 0, 0, 1)),
+            }],
+        };
+
+        let bytes = serialize(&message);
+
+        assert_eq!(&bytes[0..2], &0xABCDu16.to_be_bytes());
+        assert_eq!(bytes[2], 0b1000_0001); // QR=1, opcode=0, AA=0, TC=0, RD=1
+        assert_eq!(bytes[3], 0b0000_0000); // RA=0, rcode=0
+        assert_eq!(&bytes[4..6], &1u16.to_be_bytes()); // qdcount
+        assert_eq!(&bytes[6..8], &1u16.to_be_bytes()); // ancount
+
+        // Question section: "a.com" A IN
+        assert_eq!(&bytes[12..19], &[1, b'a', 3, b'c', b'o', b'm', 0]);
+        assert_eq!(&bytes[19..21], &1u16.to_be_bytes());
+        assert_eq!(&bytes[21..23], &1u16.to_be_bytes());
+
+        // Answer record: name, type, class, ttl, rdlength, rdata
+        assert_eq!(&bytes[23..30], &[1, b'a', 3, b'c', b'o', b'm', 0]);
+        assert_eq!(&bytes[30..32], &1u16.to_be_bytes());
+        assert_eq!(&bytes[32..34], &1u16.to_be_bytes());
+        assert_eq!(&bytes[34..38], &300u32.to_be_bytes());
+        assert_eq!(&bytes[38..40], &4u16.to_be_bytes());
+        assert_eq!(&bytes[40..44], &[127, 0, 0, 1]);
+
+        assert_eq!(bytes.len(), 44);
+    }
+
+    #[test]
+    fn a_serialized_query_round_trips_its_header_and_question_through_parse() {
+        let message = DnsMessage {
+            header: DnsHeader {
+                id: 42,
+                qr: false,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 0,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![Question { qname: "example.com".to_string(), qtype: QueryType::Aaaa, qclass: 1 }],
+            answers: Vec::new(),
+        };
+
+        let bytes = serialize(&message);
+        let reparsed = parse(&bytes).expect("should parse");
+
+        assert_eq!(reparsed.header.id, 42);
+        assert!(reparsed.header.rd);
+        assert_eq!(reparsed.questions.len(), 1);
+        assert_eq!(reparsed.questions[0].qname, "example.com");
+        assert_eq!(reparsed.questions[0].qtype, QueryType::Aaaa);
+    }
+}
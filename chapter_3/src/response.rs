@@ -0,0 +1,89 @@
+use crate::dns_message::{DnsHeader, DnsMessage, Record};
+
+/// Builds a reply to `query`: echoes its ID and question section, sets the
+/// QR bit, and attaches `answers`.
+pub fn build_response(query: &DnsMessage, answers: Vec<Record>) -> DnsMessage {
+    let header = DnsHeader {
+        id: query.header.id,
+        qr: true,
+        opcode: query.header.opcode,
+        aa: false,
+        tc: false,
+        rd: query.header.rd,
+        ra: false,
+        rcode: 0,
+        qdcount: query.header.qdcount,
+        ancount: answers.len() as u16,
+        nscount: 0,
+        arcount: 0,
+    };
+
+    DnsMessage {
+        header,
+        questions: query.questions.clone(),
+        answers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns_message::{self, OpCode, Question, QueryType, RecordData};
+    use std::net::Ipv4Addr;
+
+    fn sample_query() -> DnsMessage {
+        DnsMessage {
+            header: DnsHeader {
+                id: 0x1234,
+                qr: false,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 0,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![Question { qname: "penpal.test".to_string(), qtype: QueryType::A, qclass: 1 }],
+            answers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn echoes_id_and_question_and_sets_qr_and_ancount() {
+        let query = sample_query();
+        let answers = vec![Record {
+            name: "penpal.test".to_string(),
+            rtype: QueryType::A,
+            rclass: 1,
+            ttl: 300,
+            data: RecordData::A(Ipv4Addr::new(127, 0, 0, 1)),
+        }];
+
+        let response = build_response(&query, answers.clone());
+
+        assert_eq!(response.header.id, query.header.id);
+        assert!(response.header.qr);
+        assert_eq!(response.header.qdcount, query.header.qdcount);
+        assert_eq!(response.header.ancount, answers.len() as u16);
+        assert_eq!(response.questions.len(), 1);
+        assert_eq!(response.questions[0].qname, "penpal.test");
+        assert_eq!(response.answers, answers);
+    }
+
+    #[test]
+    fn a_built_response_round_trips_its_header_and_question_through_serialize_and_parse() {
+        let query = sample_query();
+        let response = build_response(&query, Vec::new());
+
+        let bytes = dns_message::serialize(&response);
+        let reparsed = dns_message::parse(&bytes).expect("should parse");
+
+        assert_eq!(reparsed.header.id, query.header.id);
+        assert!(reparsed.header.qr);
+        assert_eq!(reparsed.questions[0].qname, "penpal.test");
+    }
+}
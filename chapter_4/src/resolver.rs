@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::OnceLock;
+
+use crate::dns_message::{QueryType, Question, Record, RecordData};
+
+const DEFAULT_TTL: u32 = 300;
+
+/// Our (currently static) in-memory zone. A recursive or forwarding resolver
+/// would plug in here instead of, or in addition to, this table.
+fn zone() -> &'static HashMap<(String, QueryType), RecordData> {
+    static ZONE: OnceLock<HashMap<(String, QueryType), RecordData>> = OnceLock::new();
+    ZONE.get_or_init(|| {
+        let mut zone = HashMap::new();
+        zone.insert(("penpal.test".to_string(), QueryType::A), RecordData::A(Ipv4Addr::new(127, 0, 0, 1)));
+        zone.insert(("penpal.test".to_string(), QueryType::Aaaa), RecordData::Aaaa(Ipv6Addr::LOCALHOST));
+        zone
+    })
+}
+
+/// Looks up answers for `question`. This is the single extension point later
+/// resolver modes (forwarding, recursion, caching) hook into.
+pub fn resolve(question: &Question) -> Vec<Record> {
+    match zone().get(&(question.qname.clone(), question.qtype)) {
+        Some(data) => vec![Record {
+            name: question.qname.clone(),
+            rtype: question.qtype,
+            rclass: question.qclass,
+            ttl: DEFAULT_TTL,
+            data: data.clone(),
+        }],
+        None => Vec::new(),
+    }
+}
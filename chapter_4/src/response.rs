@@ -0,0 +1,36 @@
+use crate::dns_message::{self, DnsHeader, DnsMessage, Record};
+
+/// Builds a reply to `query`: echoes its ID and question section, sets the
+/// QR bit, and attaches `answers`. When `query` carried an EDNS(0) OPT
+/// record, the reply gets its own OPT record advertising
+/// `our_max_udp_payload_size`.
+pub fn build_response(query: &DnsMessage, answers: Vec<Record>, our_max_udp_payload_size: u16) -> DnsMessage {
+    let additionals = if query.edns.is_some() {
+        vec![dns_message::build_opt_record(our_max_udp_payload_size)]
+    } else {
+        Vec::new()
+    };
+
+    let header = DnsHeader {
+        id: query.header.id,
+        qr: true,
+        opcode: query.header.opcode,
+        aa: false,
+        tc: false,
+        rd: query.header.rd,
+        ra: false,
+        rcode: 0,
+        qdcount: query.header.qdcount,
+        ancount: answers.len() as u16,
+        nscount: 0,
+        arcount: additionals.len() as u16,
+    };
+
+    DnsMessage {
+        header,
+        questions: query.questions.clone(),
+        answers,
+        additionals,
+        edns: None,
+    }
+}
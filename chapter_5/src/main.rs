@@ -0,0 +1,58 @@
+mod dns_message;
+mod resolver;
+mod response;
+mod tcp;
+
+use std::net::{TcpListener, UdpSocket};
+
+/// The legacy DNS-over-UDP limit, still our floor when a query carries no
+/// EDNS(0) OPT record.
+const MAX_DNS_UDP_PACKET_SIZE: usize = 512;
+
+/// RFC 6891 recommends capping advertised (and accepted) EDNS(0) UDP payload
+/// sizes at 4096 bytes, even though the field technically allows larger.
+const EDNS_MAX_UDP_PAYLOAD_SIZE: u16 = 4096;
+
+fn main() {
+    let socket = UdpSocket::bind("0.0.0.0:0").expect("Failed to bind to our local DNS port");
+    println!("Bound to {socket:?}");
+
+    let local_addr = socket.local_addr().expect("Failed to read our local address");
+    let tcp_listener = TcpListener::bind(local_addr).expect("Failed to bind our TCP DNS port");
+    std::thread::spawn(move || tcp::serve(tcp_listener, EDNS_MAX_UDP_PAYLOAD_SIZE));
+
+    let mut receive_packet_buf = [0; EDNS_MAX_UDP_PAYLOAD_SIZE as usize];
+    println!("Awaiting incoming packets...");
+    loop {
+        let (byte_count_received, sender_addr) = socket.recv_from(&mut receive_packet_buf).expect("Failed to read from the socket");
+        println!("We've received a DNS query of {byte_count_received} bytes from {sender_addr:?}");
+
+        let query = match dns_message::parse(&receive_packet_buf[..byte_count_received]) {
+            Ok(query) => query,
+            Err(err) => {
+                println!("  Failed to parse query: {err}");
+                continue;
+            }
+        };
+        println!("  Header: {:?}", query.header);
+
+        let peer_udp_payload_size = query
+            .edns
+            .map(|edns| edns.requestor_payload_size.clamp(MAX_DNS_UDP_PACKET_SIZE as u16, EDNS_MAX_UDP_PAYLOAD_SIZE))
+            .unwrap_or(MAX_DNS_UDP_PACKET_SIZE as u16);
+
+        let answers: Vec<_> = query.questions.iter().flat_map(resolver::resolve).collect();
+        let response = response::build_response(&query, answers, EDNS_MAX_UDP_PAYLOAD_SIZE);
+
+        let mut response_buf = dns_message::serialize(&response);
+        if response_buf.len() > peer_udp_payload_size as usize {
+            println!(
+                "  Response of {} bytes exceeds the negotiated {peer_udp_payload_size} byte limit; signalling truncation",
+                response_buf.len()
+            );
+            response_buf = dns_message::serialize(&response::build_truncated(&query));
+        }
+
+        socket.send_to(&response_buf, sender_addr).expect("Failed to send our response");
+    }
+}
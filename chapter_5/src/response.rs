@@ -0,0 +1,116 @@
+use crate::dns_message::{self, DnsHeader, DnsMessage, Record};
+
+/// Builds a reply to `query`: echoes its ID and question section, sets the
+/// QR bit, and attaches `answers`. When `query` carried an EDNS(0) OPT
+/// record, the reply gets its own OPT record advertising
+/// `our_max_udp_payload_size`.
+pub fn build_response(query: &DnsMessage, answers: Vec<Record>, our_max_udp_payload_size: u16) -> DnsMessage {
+    let additionals = if query.edns.is_some() {
+        vec![dns_message::build_opt_record(our_max_udp_payload_size)]
+    } else {
+        Vec::new()
+    };
+
+    let header = DnsHeader {
+        id: query.header.id,
+        qr: true,
+        opcode: query.header.opcode,
+        aa: false,
+        tc: false,
+        rd: query.header.rd,
+        ra: false,
+        rcode: 0,
+        qdcount: query.header.qdcount,
+        ancount: answers.len() as u16,
+        nscount: 0,
+        arcount: additionals.len() as u16,
+    };
+
+    DnsMessage {
+        header,
+        questions: query.questions.clone(),
+        answers,
+        additionals,
+        edns: None,
+    }
+}
+
+/// Builds a truncated reply: the TC bit is set and the answer/additional
+/// sections are dropped entirely, signalling the client to retry over TCP.
+pub fn build_truncated(query: &DnsMessage) -> DnsMessage {
+    let header = DnsHeader {
+        id: query.header.id,
+        qr: true,
+        opcode: query.header.opcode,
+        aa: false,
+        tc: true,
+        rd: query.header.rd,
+        ra: false,
+        rcode: 0,
+        qdcount: query.header.qdcount,
+        ancount: 0,
+        nscount: 0,
+        arcount: 0,
+    };
+
+    DnsMessage {
+        header,
+        questions: query.questions.clone(),
+        answers: Vec::new(),
+        additionals: Vec::new(),
+        edns: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns_message::{OpCode, Question, QueryType};
+
+    fn sample_query() -> DnsMessage {
+        DnsMessage {
+            header: DnsHeader {
+                id: 0x1234,
+                qr: false,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 0,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![Question { qname: "penpal.test".to_string(), qtype: QueryType::A, qclass: 1 }],
+            answers: Vec::new(),
+            additionals: Vec::new(),
+            edns: None,
+        }
+    }
+
+    #[test]
+    fn build_truncated_sets_tc_and_drops_all_records() {
+        let query = sample_query();
+        let truncated = build_truncated(&query);
+
+        assert_eq!(truncated.header.id, query.header.id);
+        assert!(truncated.header.qr);
+        assert!(truncated.header.tc);
+        assert_eq!(truncated.header.qdcount, query.header.qdcount);
+        assert_eq!(truncated.header.ancount, 0);
+        assert_eq!(truncated.header.arcount, 0);
+        assert_eq!(truncated.questions.len(), 1);
+        assert_eq!(truncated.answers.len(), 0);
+        assert_eq!(truncated.additionals.len(), 0);
+    }
+
+    #[test]
+    fn a_truncated_response_serializes_under_the_legacy_udp_limit() {
+        let truncated = build_truncated(&sample_query());
+        let bytes = dns_message::serialize(&truncated);
+
+        assert!(bytes.len() <= 512);
+    }
+}
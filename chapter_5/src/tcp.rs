@@ -0,0 +1,108 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::{dns_message, resolver, response};
+
+/// Accepts DNS-over-TCP connections and serves each on its own thread.
+/// RFC 1035 §4.2.2 prefixes every TCP message with a 2-byte big-endian
+/// length, which is how a client ends up here after receiving a truncated
+/// UDP response.
+pub fn serve(listener: TcpListener, our_max_udp_payload_size: u16) {
+    for incoming in listener.incoming() {
+        let stream = match incoming {
+            Ok(stream) => stream,
+            Err(err) => {
+                println!("Failed to accept a TCP connection: {err}");
+                continue;
+            }
+        };
+        std::thread::spawn(move || handle_connection(stream, our_max_udp_payload_size));
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, our_max_udp_payload_size: u16) {
+    loop {
+        let mut length_prefix = [0u8; 2];
+        if stream.read_exact(&mut length_prefix).is_err() {
+            return;
+        }
+        let message_len = u16::from_be_bytes(length_prefix) as usize;
+
+        let mut message_buf = vec![0u8; message_len];
+        if stream.read_exact(&mut message_buf).is_err() {
+            return;
+        }
+
+        let query = match dns_message::parse(&message_buf) {
+            Ok(query) => query,
+            Err(err) => {
+                println!("Failed to parse a TCP query: {err}");
+                return;
+            }
+        };
+
+        let answers: Vec<_> = query.questions.iter().flat_map(resolver::resolve).collect();
+        let response = response::build_response(&query, answers, our_max_udp_payload_size);
+        let response_buf = dns_message::serialize(&response);
+
+        let response_length_prefix = (response_buf.len() as u16).to_be_bytes();
+        if stream.write_all(&response_length_prefix).is_err() || stream.write_all(&response_buf).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns_message::{DnsHeader, OpCode, Question, QueryType};
+
+    fn build_query_bytes() -> Vec<u8> {
+        let message = dns_message::DnsMessage {
+            header: DnsHeader {
+                id: 0xBEEF,
+                qr: false,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 0,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![Question { qname: "penpal.test".to_string(), qtype: QueryType::A, qclass: 1 }],
+            answers: Vec::new(),
+            additionals: Vec::new(),
+            edns: None,
+        };
+        dns_message::serialize(&message)
+    }
+
+    #[test]
+    fn length_prefix_framing_round_trips_a_query_and_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind a test TCP listener");
+        let local_addr = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || serve(listener, 4096));
+
+        let mut client = TcpStream::connect(local_addr).expect("failed to connect to the test listener");
+
+        let query_buf = build_query_bytes();
+        client.write_all(&(query_buf.len() as u16).to_be_bytes()).unwrap();
+        client.write_all(&query_buf).unwrap();
+
+        let mut length_prefix = [0u8; 2];
+        client.read_exact(&mut length_prefix).unwrap();
+        let response_len = u16::from_be_bytes(length_prefix) as usize;
+
+        let mut response_buf = vec![0u8; response_len];
+        client.read_exact(&mut response_buf).unwrap();
+
+        let response = dns_message::parse(&response_buf).expect("response should parse");
+        assert_eq!(response.header.id, 0xBEEF);
+        assert!(response.header.qr);
+    }
+}
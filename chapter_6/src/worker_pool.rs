@@ -0,0 +1,124 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{dns_message, resolver, response};
+
+/// A single datagram handed from the receive loop to a worker: the raw bytes
+/// (copied out of the shared receive buffer) and who sent it.
+pub type Job = (Vec<u8>, SocketAddr);
+
+/// Spawns `pool_size` worker threads, each owning a cloned sending handle on
+/// `socket`, and returns the channel the receive loop should hand incoming
+/// datagrams off to. `min_udp_payload_size`/`max_udp_payload_size` bound the
+/// EDNS(0)-negotiated response size, same as the single-threaded loop used.
+pub fn spawn(pool_size: usize, socket: &UdpSocket, min_udp_payload_size: u16, max_udp_payload_size: u16) -> Sender<Job> {
+    let (tx, rx) = mpsc::channel::<Job>();
+    let rx = Arc::new(Mutex::new(rx));
+
+    for worker_id in 0..pool_size {
+        let rx = Arc::clone(&rx);
+        let socket = socket.try_clone().expect("Failed to clone our UDP socket for a worker thread");
+        thread::spawn(move || worker_loop(worker_id, rx, socket, min_udp_payload_size, max_udp_payload_size));
+    }
+
+    tx
+}
+
+fn worker_loop(worker_id: usize, rx: Arc<Mutex<Receiver<Job>>>, socket: UdpSocket, min_udp_payload_size: u16, max_udp_payload_size: u16) {
+    loop {
+        let (packet, sender_addr) = match rx.lock().expect("Worker pool job queue poisoned").recv() {
+            Ok(job) => job,
+            Err(_) => return, // The sending half was dropped; nothing left to do.
+        };
+
+        handle_query(worker_id, &socket, &packet, sender_addr, min_udp_payload_size, max_udp_payload_size);
+    }
+}
+
+fn handle_query(worker_id: usize, socket: &UdpSocket, packet: &[u8], sender_addr: SocketAddr, min_udp_payload_size: u16, max_udp_payload_size: u16) {
+    let query = match dns_message::parse(packet) {
+        Ok(query) => query,
+        Err(err) => {
+            println!("[worker {worker_id}] Failed to parse query from {sender_addr:?}: {err}");
+            return;
+        }
+    };
+    println!("[worker {worker_id}] Header: {:?}", query.header);
+
+    let peer_udp_payload_size = query
+        .edns
+        .map(|edns| edns.requestor_payload_size.clamp(min_udp_payload_size, max_udp_payload_size))
+        .unwrap_or(min_udp_payload_size);
+
+    let answers: Vec<_> = query.questions.iter().flat_map(resolver::resolve).collect();
+    let response = response::build_response(&query, answers, max_udp_payload_size);
+
+    let mut response_buf = dns_message::serialize(&response);
+    if response_buf.len() > peer_udp_payload_size as usize {
+        response_buf = dns_message::serialize(&response::build_truncated(&query));
+    }
+
+    if let Err(err) = socket.send_to(&response_buf, sender_addr) {
+        println!("[worker {worker_id}] Failed to send response to {sender_addr:?}: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns_message::{DnsHeader, DnsMessage, OpCode, Question, QueryType};
+    use std::time::Duration;
+
+    fn build_query(id: u16, qname: &str) -> Vec<u8> {
+        let message = DnsMessage {
+            header: DnsHeader {
+                id,
+                qr: false,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 0,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![Question { qname: qname.to_string(), qtype: QueryType::A, qclass: 1 }],
+            answers: Vec::new(),
+            additionals: Vec::new(),
+            edns: None,
+        };
+        dns_message::serialize(&message)
+    }
+
+    #[test]
+    fn concurrent_jobs_are_each_answered_with_their_own_query_id() {
+        let server_socket = UdpSocket::bind("127.0.0.1:0").expect("failed to bind a test server socket");
+        let client_socket = UdpSocket::bind("127.0.0.1:0").expect("failed to bind a test client socket");
+        client_socket.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let client_addr = client_socket.local_addr().unwrap();
+
+        let job_tx = spawn(2, &server_socket, 512, 4096);
+
+        // Two distinct queries, handed off as distinct owned buffers -- if a
+        // worker ever aliased another job's bytes, one of these IDs would
+        // come back wrong (or not at all).
+        job_tx.send((build_query(1, "penpal.test"), client_addr)).unwrap();
+        job_tx.send((build_query(2, "penpal.test"), client_addr)).unwrap();
+
+        let mut seen_ids = Vec::new();
+        for _ in 0..2 {
+            let mut buf = [0u8; 512];
+            let (byte_count_received, _) = client_socket.recv_from(&mut buf).expect("expected a response for each job");
+            let response = dns_message::parse(&buf[..byte_count_received]).expect("response should parse");
+            seen_ids.push(response.header.id);
+        }
+        seen_ids.sort();
+
+        assert_eq!(seen_ids, vec![1, 2]);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::dns_message::{QueryType, Record};
+
+struct CacheEntry {
+    records: Vec<Record>,
+    expires_at: Instant,
+}
+
+fn store() -> &'static Mutex<HashMap<(String, QueryType), CacheEntry>> {
+    static STORE: OnceLock<Mutex<HashMap<(String, QueryType), CacheEntry>>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns cached records for `(name, qtype)`, or `None` if we have nothing
+/// cached or what we had has expired.
+pub fn get(name: &str, qtype: QueryType) -> Option<Vec<Record>> {
+    let key = (name.to_string(), qtype);
+    let mut store = store().lock().expect("DNS cache poisoned");
+
+    match store.get(&key) {
+        Some(entry) if entry.expires_at > Instant::now() => Some(entry.records.clone()),
+        Some(_) => {
+            store.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Caches `records` for `(name, qtype)`, honoring the TTL of the
+/// shortest-lived record in the set.
+pub fn put(name: &str, qtype: QueryType, records: Vec<Record>) {
+    let Some(min_ttl) = records.iter().map(|record| record.ttl).min() else {
+        return;
+    };
+
+    let entry = CacheEntry {
+        records,
+        expires_at: Instant::now() + Duration::from_secs(min_ttl as u64),
+    };
+    store().lock().expect("DNS cache poisoned").insert((name.to_string(), qtype), entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns_message::RecordData;
+    use std::net::Ipv4Addr;
+    use std::thread::sleep;
+
+    fn sample_record(ttl: u32) -> Record {
+        Record {
+            name: "cache-test.example".to_string(),
+            rtype: QueryType::A,
+            rclass: 1,
+            ttl,
+            data: RecordData::A(Ipv4Addr::new(127, 0, 0, 1)),
+        }
+    }
+
+    #[test]
+    fn returns_none_for_a_name_that_was_never_cached() {
+        assert!(get("never-cached.example", QueryType::A).is_none());
+    }
+
+    #[test]
+    fn put_then_get_returns_the_same_records() {
+        let name = "put-then-get.example";
+        put(name, QueryType::A, vec![sample_record(300)]);
+
+        let cached = get(name, QueryType::A).expect("should have cached records");
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].data, RecordData::A(Ipv4Addr::new(127, 0, 0, 1)));
+    }
+
+    #[test]
+    fn an_entry_with_an_expired_ttl_is_evicted_on_read() {
+        let name = "expired.example";
+        put(name, QueryType::A, vec![sample_record(0)]);
+
+        // A 0-second TTL expires immediately; the clock still needs a moment
+        // to tick past `expires_at`.
+        sleep(Duration::from_millis(5));
+
+        assert!(get(name, QueryType::A).is_none());
+        // The stale entry should have been removed, not just skipped.
+        assert!(store().lock().unwrap().get(&(name.to_string(), QueryType::A)).is_none());
+    }
+
+    #[test]
+    fn put_uses_the_shortest_ttl_among_the_cached_records() {
+        let name = "min-ttl.example";
+        put(name, QueryType::A, vec![sample_record(300), sample_record(0)]);
+
+        // The 0-second record should have dragged the whole entry's expiry
+        // down with it.
+        sleep(Duration::from_millis(5));
+        assert!(get(name, QueryType::A).is_none());
+    }
+}
@@ -0,0 +1,418 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// The DNS header is always exactly 12 bytes, regardless of transport.
+const HEADER_SIZE: usize = 12;
+
+/// RR TYPE value for the EDNS(0) OPT pseudo-record (RFC 6891).
+const OPT_RR_TYPE: u16 = 41;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    Query,
+    IQuery,
+    Status,
+    Reserved(u8),
+}
+
+impl OpCode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => OpCode::Query,
+            1 => OpCode::IQuery,
+            2 => OpCode::Status,
+            other => OpCode::Reserved(other),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            OpCode::Query => 0,
+            OpCode::IQuery => 1,
+            OpCode::Status => 2,
+            OpCode::Reserved(other) => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryType {
+    A,
+    Ns,
+    Cname,
+    Soa,
+    Aaaa,
+    /// The EDNS(0) OPT pseudo-record; not a real query type, but it's encoded
+    /// in the TYPE field the same way.
+    Opt,
+    Other(u16),
+}
+
+impl QueryType {
+    fn from_u16(value: u16) -> Self {
+        match value {
+            1 => QueryType::A,
+            2 => QueryType::Ns,
+            5 => QueryType::Cname,
+            6 => QueryType::Soa,
+            28 => QueryType::Aaaa,
+            OPT_RR_TYPE => QueryType::Opt,
+            other => QueryType::Other(other),
+        }
+    }
+
+    pub fn to_u16(self) -> u16 {
+        match self {
+            QueryType::A => 1,
+            QueryType::Ns => 2,
+            QueryType::Cname => 5,
+            QueryType::Soa => 6,
+            QueryType::Aaaa => 28,
+            QueryType::Opt => OPT_RR_TYPE,
+            QueryType::Other(other) => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DnsHeader {
+    pub id: u16,
+    pub qr: bool,
+    pub opcode: OpCode,
+    pub aa: bool,
+    pub tc: bool,
+    pub rd: bool,
+    pub ra: bool,
+    pub rcode: u8,
+    pub qdcount: u16,
+    pub ancount: u16,
+    pub nscount: u16,
+    pub arcount: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub qname: String,
+    pub qtype: QueryType,
+    pub qclass: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordData {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    /// We don't support any EDNS options ourselves, so our OPT records never
+    /// carry an options list.
+    Opt,
+}
+
+impl RecordData {
+    fn rdata_bytes(&self) -> Vec<u8> {
+        match self {
+            RecordData::A(addr) => addr.octets().to_vec(),
+            RecordData::Aaaa(addr) => addr.octets().to_vec(),
+            RecordData::Opt => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub rtype: QueryType,
+    /// For an OPT record this field carries the requestor's (or our own)
+    /// advertised UDP payload size rather than a real CLASS value.
+    pub rclass: u16,
+    /// For an OPT record this field carries the packed extended
+    /// RCODE/VERSION/flags rather than a real TTL.
+    pub ttl: u32,
+    pub data: RecordData,
+}
+
+/// A parsed EDNS(0) OPT pseudo-record, per RFC 6891. We only care about the
+/// requestor's advertised UDP payload size; we don't currently act on the
+/// extended RCODE/version/flags bits.
+#[derive(Debug, Clone, Copy)]
+pub struct EdnsOpt {
+    pub requestor_payload_size: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsMessage {
+    pub header: DnsHeader,
+    pub questions: Vec<Question>,
+    pub answers: Vec<Record>,
+    pub additionals: Vec<Record>,
+    pub edns: Option<EdnsOpt>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The buffer is shorter than a DNS header.
+    PacketTooShort,
+    /// We ran off the end of the buffer while decoding a name or a fixed field.
+    UnexpectedEof,
+    /// A compression pointer pointed at or past its own position.
+    PointerLoop,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::PacketTooShort => write!(f, "packet is shorter than a DNS header"),
+            ParseError::UnexpectedEof => write!(f, "ran off the end of the packet while parsing"),
+            ParseError::PointerLoop => write!(f, "name compression pointer does not point backwards"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(buf: &[u8]) -> Result<DnsMessage, ParseError> {
+    if buf.len() < HEADER_SIZE {
+        return Err(ParseError::PacketTooShort);
+    }
+
+    let header = parse_header(buf);
+
+    let mut pos = HEADER_SIZE;
+    // `header.qdcount` is an unvalidated 16-bit field from the wire; pre-allocating
+    // from it directly would let a single short datagram claiming qdcount = 0xFFFF
+    // force a large allocation that's immediately discarded on the first parse error.
+    let mut questions = Vec::new();
+    for _ in 0..header.qdcount {
+        let (qname, name_len) = read_name(buf, pos)?;
+        pos += name_len;
+
+        let qtype = QueryType::from_u16(read_u16(buf, pos)?);
+        pos += 2;
+        let qclass = read_u16(buf, pos)?;
+        pos += 2;
+
+        questions.push(Question { qname, qtype, qclass });
+    }
+
+    // A query's answer section is always empty, but a reply from an upstream
+    // resolver (see `forwarder`) carries real answers here, so unlike the
+    // authority/additional sections we decode rather than just skip past it.
+    let mut answers = Vec::new();
+    for _ in 0..header.ancount {
+        let (raw, next_pos) = read_resource_record(buf, pos)?;
+        pos = next_pos;
+
+        let rtype = QueryType::from_u16(raw.rtype);
+        if let Some(data) = decode_record_data(rtype, &raw.rdata) {
+            answers.push(Record { name: raw.name, rtype, rclass: raw.rclass, ttl: raw.ttl, data });
+        }
+    }
+    for _ in 0..header.nscount {
+        let (_, next_pos) = read_resource_record(buf, pos)?;
+        pos = next_pos;
+    }
+
+    let mut edns = None;
+    for _ in 0..header.arcount {
+        let (record, next_pos) = read_resource_record(buf, pos)?;
+        if record.rtype == OPT_RR_TYPE {
+            edns = Some(EdnsOpt { requestor_payload_size: record.rclass });
+        }
+        pos = next_pos;
+    }
+
+    Ok(DnsMessage { header, questions, answers, additionals: Vec::new(), edns })
+}
+
+fn parse_header(buf: &[u8]) -> DnsHeader {
+    let id = u16::from_be_bytes([buf[0], buf[1]]);
+    let flags_hi = buf[2];
+    let flags_lo = buf[3];
+
+    DnsHeader {
+        id,
+        qr: flags_hi & 0b1000_0000 != 0,
+        opcode: OpCode::from_u8((flags_hi >> 3) & 0b0000_1111),
+        aa: flags_hi & 0b0000_0100 != 0,
+        tc: flags_hi & 0b0000_0010 != 0,
+        rd: flags_hi & 0b0000_0001 != 0,
+        ra: flags_lo & 0b1000_0000 != 0,
+        rcode: flags_lo & 0b0000_1111,
+        qdcount: u16::from_be_bytes([buf[4], buf[5]]),
+        ancount: u16::from_be_bytes([buf[6], buf[7]]),
+        nscount: u16::from_be_bytes([buf[8], buf[9]]),
+        arcount: u16::from_be_bytes([buf[10], buf[11]]),
+    }
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16, ParseError> {
+    let bytes = buf.get(pos..pos + 2).ok_or(ParseError::UnexpectedEof)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32, ParseError> {
+    let bytes = buf.get(pos..pos + 4).ok_or(ParseError::UnexpectedEof)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// A resource record with its RDATA left un-interpreted, used while walking
+/// sections where the caller either just needs to skip past the record (or,
+/// for OPT, peek at its CLASS field) or decode its RDATA itself.
+struct RawRecord {
+    name: String,
+    rtype: u16,
+    rclass: u16,
+    ttl: u32,
+    rdata: Vec<u8>,
+}
+
+fn read_resource_record(buf: &[u8], pos: usize) -> Result<(RawRecord, usize), ParseError> {
+    let (name, name_len) = read_name(buf, pos)?;
+    let mut pos = pos + name_len;
+
+    let rtype = read_u16(buf, pos)?;
+    pos += 2;
+    let rclass = read_u16(buf, pos)?;
+    pos += 2;
+    let ttl = read_u32(buf, pos)?;
+    pos += 4;
+    let rdlength = read_u16(buf, pos)? as usize;
+    pos += 2;
+
+    let rdata = buf.get(pos..pos + rdlength).ok_or(ParseError::UnexpectedEof)?.to_vec();
+    pos += rdlength;
+
+    Ok((RawRecord { name, rtype, rclass, ttl, rdata }, pos))
+}
+
+/// Decodes a record's RDATA into the `RecordData` variant matching `rtype`,
+/// or `None` for a type/length combination we don't model -- the caller
+/// drops such records rather than guessing at their shape.
+fn decode_record_data(rtype: QueryType, rdata: &[u8]) -> Option<RecordData> {
+    match (rtype, rdata.len()) {
+        (QueryType::A, 4) => Some(RecordData::A(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))),
+        (QueryType::Aaaa, 16) => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(rdata);
+            Some(RecordData::Aaaa(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// Decodes a (possibly compressed) name starting at `start`, returning the
+/// decoded dotted name and the number of bytes consumed from `start` in the
+/// *original* message (i.e. not counting any bytes read after following a
+/// compression pointer).
+fn read_name(buf: &[u8], start: usize) -> Result<(String, usize), ParseError> {
+    let mut labels = Vec::new();
+    let mut pos = start;
+    let mut consumed_to: Option<usize> = None;
+
+    loop {
+        let length_byte = *buf.get(pos).ok_or(ParseError::UnexpectedEof)?;
+
+        if length_byte & 0xC0 == 0xC0 {
+            let lo = *buf.get(pos + 1).ok_or(ParseError::UnexpectedEof)?;
+            let pointer = (((length_byte & 0x3F) as usize) << 8) | lo as usize;
+
+            // A pointer must point strictly backwards, otherwise we'd either
+            // loop forever or read into the part of the message we haven't
+            // parsed yet.
+            if pointer >= pos {
+                return Err(ParseError::PointerLoop);
+            }
+
+            if consumed_to.is_none() {
+                consumed_to = Some(pos + 2);
+            }
+            pos = pointer;
+            continue;
+        }
+
+        if length_byte == 0 {
+            if consumed_to.is_none() {
+                consumed_to = Some(pos + 1);
+            }
+            break;
+        }
+
+        let label_len = length_byte as usize;
+        pos += 1;
+        let label_bytes = buf.get(pos..pos + label_len).ok_or(ParseError::UnexpectedEof)?;
+        labels.push(String::from_utf8_lossy(label_bytes).into_owned());
+        pos += label_len;
+    }
+
+    Ok((labels.join("."), consumed_to.unwrap() - start))
+}
+
+/// Encodes `name` as a sequence of length-prefixed labels terminated by a
+/// zero byte. We never emit compression pointers of our own; keeping
+/// serialization simple is worth a few extra bytes on the wire.
+fn encode_name(name: &str, out: &mut Vec<u8>) {
+    if !name.is_empty() {
+        for label in name.split('.') {
+            out.push(label.len() as u8);
+            out.extend_from_slice(label.as_bytes());
+        }
+    }
+    out.push(0);
+}
+
+fn write_record(record: &Record, out: &mut Vec<u8>) {
+    encode_name(&record.name, out);
+    out.extend_from_slice(&record.rtype.to_u16().to_be_bytes());
+    out.extend_from_slice(&record.rclass.to_be_bytes());
+    out.extend_from_slice(&record.ttl.to_be_bytes());
+
+    let rdata = record.data.rdata_bytes();
+    out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    out.extend_from_slice(&rdata);
+}
+
+/// Serializes `message` back into wire format.
+pub fn serialize(message: &DnsMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    out.extend_from_slice(&message.header.id.to_be_bytes());
+
+    let flags_hi = (message.header.qr as u8) << 7
+        | message.header.opcode.to_u8() << 3
+        | (message.header.aa as u8) << 2
+        | (message.header.tc as u8) << 1
+        | message.header.rd as u8;
+    let flags_lo = (message.header.ra as u8) << 7 | (message.header.rcode & 0b0000_1111);
+    out.push(flags_hi);
+    out.push(flags_lo);
+
+    out.extend_from_slice(&message.header.qdcount.to_be_bytes());
+    out.extend_from_slice(&message.header.ancount.to_be_bytes());
+    out.extend_from_slice(&message.header.nscount.to_be_bytes());
+    out.extend_from_slice(&message.header.arcount.to_be_bytes());
+
+    for question in &message.questions {
+        encode_name(&question.qname, &mut out);
+        out.extend_from_slice(&question.qtype.to_u16().to_be_bytes());
+        out.extend_from_slice(&question.qclass.to_be_bytes());
+    }
+
+    for record in &message.answers {
+        write_record(record, &mut out);
+    }
+    for record in &message.additionals {
+        write_record(record, &mut out);
+    }
+
+    out
+}
+
+/// Builds the OPT pseudo-record we attach to responses to advertise our own
+/// maximum UDP payload size.
+pub fn build_opt_record(our_max_udp_payload_size: u16) -> Record {
+    Record {
+        name: String::new(),
+        rtype: QueryType::Opt,
+        rclass: our_max_udp_payload_size,
+        ttl: 0,
+        data: RecordData::Opt,
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::dns_message::{self, DnsHeader, DnsMessage, OpCode, ParseError, Question, Record};
+
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+const FORWARD_RETRIES: usize = 2;
+
+#[derive(Debug)]
+pub enum ForwardError {
+    Io(std::io::Error),
+    Parse(ParseError),
+    /// We exhausted our retries without a reply whose transaction ID
+    /// matched our query.
+    NoResponse,
+}
+
+impl fmt::Display for ForwardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForwardError::Io(err) => write!(f, "I/O error talking to upstream: {err}"),
+            ForwardError::Parse(err) => write!(f, "upstream sent an unparseable response: {err}"),
+            ForwardError::NoResponse => write!(f, "upstream did not answer in time"),
+        }
+    }
+}
+
+impl std::error::Error for ForwardError {}
+
+/// Forwards `question` to `upstream` as a fresh query and returns whatever
+/// answers it replies with, retrying up to `FORWARD_RETRIES` times when an
+/// attempt times out or is answered with a mismatched transaction ID.
+pub fn forward(upstream: SocketAddr, question: &Question) -> Result<Vec<Record>, ForwardError> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(ForwardError::Io)?;
+    socket.set_read_timeout(Some(FORWARD_TIMEOUT)).map_err(ForwardError::Io)?;
+
+    let txid = random_transaction_id();
+    let query_buf = dns_message::serialize(&build_query(txid, question));
+
+    let mut receive_buf = [0u8; 4096];
+    for attempt in 0..=FORWARD_RETRIES {
+        socket.send_to(&query_buf, upstream).map_err(ForwardError::Io)?;
+
+        match socket.recv_from(&mut receive_buf) {
+            Ok((byte_count_received, _)) => {
+                let response = dns_message::parse(&receive_buf[..byte_count_received]).map_err(ForwardError::Parse)?;
+                if response.header.id == txid {
+                    return Ok(response.answers);
+                }
+                // A stale reply to an earlier, already-abandoned query; move on
+                // to the next attempt, which sends the query again.
+            }
+            Err(err) if is_timeout(&err) => {
+                // Out of retries: a timeout here means upstream never
+                // answered, which is `NoResponse`, not an I/O failure.
+                if attempt == FORWARD_RETRIES {
+                    return Err(ForwardError::NoResponse);
+                }
+                continue;
+            }
+            Err(err) => return Err(ForwardError::Io(err)),
+        }
+    }
+
+    Err(ForwardError::NoResponse)
+}
+
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(err.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut)
+}
+
+fn build_query(id: u16, question: &Question) -> DnsMessage {
+    DnsMessage {
+        header: DnsHeader {
+            id,
+            qr: false,
+            opcode: OpCode::Query,
+            aa: false,
+            tc: false,
+            rd: true,
+            ra: false,
+            rcode: 0,
+            qdcount: 1,
+            ancount: 0,
+            nscount: 0,
+            arcount: 0,
+        },
+        questions: vec![question.clone()],
+        answers: Vec::new(),
+        additionals: Vec::new(),
+        edns: None,
+    }
+}
+
+/// A random 16-bit transaction ID. We have no `rand` dependency here, so we
+/// fold a monotonic counter and the current instant through a hasher; this
+/// only needs to be unpredictable enough to make off-path response spoofing
+/// harder, not cryptographically secure.
+fn random_transaction_id() -> u16 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = DefaultHasher::new();
+    Instant::now().hash(&mut hasher);
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    hasher.finish() as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns_message::{QueryType, RecordData};
+    use std::net::Ipv4Addr;
+
+    fn sample_question() -> Question {
+        Question { qname: "penpal.test".to_string(), qtype: QueryType::A, qclass: 1 }
+    }
+
+    fn build_reply(id: u16, question: &Question) -> Vec<u8> {
+        dns_message::serialize(&DnsMessage {
+            header: DnsHeader {
+                id,
+                qr: true,
+                opcode: OpCode::Query,
+                aa: false,
+                tc: false,
+                rd: true,
+                ra: false,
+                rcode: 0,
+                qdcount: 1,
+                ancount: 1,
+                nscount: 0,
+                arcount: 0,
+            },
+            questions: vec![question.clone()],
+            answers: vec![Record {
+                name: question.qname.clone(),
+                rtype: QueryType::A,
+                rclass: 1,
+                ttl: 300,
+                data: RecordData::A(Ipv4Addr::new(127, 0, 0, 1)),
+            }],
+            additionals: Vec::new(),
+            edns: None,
+        })
+    }
+
+    #[test]
+    fn returns_the_answers_from_a_reply_matching_our_transaction_id() {
+        let upstream = UdpSocket::bind("127.0.0.1:0").expect("failed to bind a fake upstream socket");
+        let upstream_addr = upstream.local_addr().unwrap();
+        let question = sample_question();
+
+        let worker_question = question.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (n, peer) = upstream.recv_from(&mut buf).expect("expected a forwarded query");
+            let query = dns_message::parse(&buf[..n]).expect("forwarded query should parse");
+            upstream.send_to(&build_reply(query.header.id, &worker_question), peer).unwrap();
+        });
+
+        let answers = forward(upstream_addr, &question).expect("forward should succeed");
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].data, RecordData::A(Ipv4Addr::new(127, 0, 0, 1)));
+    }
+
+    #[test]
+    fn retries_past_a_stale_reply_with_a_mismatched_transaction_id() {
+        let upstream = UdpSocket::bind("127.0.0.1:0").expect("failed to bind a fake upstream socket");
+        let upstream_addr = upstream.local_addr().unwrap();
+        let question = sample_question();
+
+        let worker_question = question.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+
+            let (n, peer) = upstream.recv_from(&mut buf).expect("expected the first forwarded query");
+            let query = dns_message::parse(&buf[..n]).expect("forwarded query should parse");
+            // A stale reply for some earlier, already-abandoned query.
+            upstream.send_to(&build_reply(query.header.id.wrapping_add(1), &worker_question), peer).unwrap();
+
+            let (n, peer) = upstream.recv_from(&mut buf).expect("expected a retried query");
+            let query = dns_message::parse(&buf[..n]).expect("retried query should parse");
+            upstream.send_to(&build_reply(query.header.id, &worker_question), peer).unwrap();
+        });
+
+        let answers = forward(upstream_addr, &question).expect("forward should succeed after retrying");
+        assert_eq!(answers.len(), 1);
+    }
+
+    #[test]
+    fn an_upstream_that_never_replies_yields_no_response_not_an_io_error() {
+        // Bind a socket to reserve an address but never read from or respond
+        // on it, so every attempt -- including the last -- times out.
+        let reserved = UdpSocket::bind("127.0.0.1:0").expect("failed to bind a fake upstream socket");
+        let upstream_addr = reserved.local_addr().unwrap();
+
+        let result = forward(upstream_addr, &sample_question());
+
+        assert!(matches!(result, Err(ForwardError::NoResponse)));
+    }
+}
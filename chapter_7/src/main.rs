@@ -0,0 +1,54 @@
+mod cache;
+mod dns_message;
+mod forwarder;
+mod resolver;
+mod response;
+mod tcp;
+mod worker_pool;
+
+use std::net::{TcpListener, UdpSocket};
+
+/// The legacy DNS-over-UDP limit, still our floor when a query carries no
+/// EDNS(0) OPT record.
+const MAX_DNS_UDP_PACKET_SIZE: usize = 512;
+
+/// RFC 6891 recommends capping advertised (and accepted) EDNS(0) UDP payload
+/// sizes at 4096 bytes, even though the field technically allows larger.
+const EDNS_MAX_UDP_PAYLOAD_SIZE: u16 = 4096;
+
+/// How many worker threads process queries concurrently. A slow resolve
+/// (e.g. an upstream lookup) on one query shouldn't block every other
+/// client.
+const WORKER_POOL_SIZE: usize = 4;
+
+/// When set, names outside our static zone are forwarded here instead of
+/// answering with nothing.
+const UPSTREAM_RESOLVER: Option<&str> = Some("1.1.1.1:53");
+
+fn main() {
+    let upstream = UPSTREAM_RESOLVER.map(|addr| addr.parse().expect("UPSTREAM_RESOLVER must be a valid socket address"));
+    resolver::set_upstream_resolver(upstream);
+
+    let socket = UdpSocket::bind("0.0.0.0:0").expect("Failed to bind to our local DNS port");
+    println!("Bound to {socket:?}");
+
+    let local_addr = socket.local_addr().expect("Failed to read our local address");
+    let tcp_listener = TcpListener::bind(local_addr).expect("Failed to bind our TCP DNS port");
+    std::thread::spawn(move || tcp::serve(tcp_listener, EDNS_MAX_UDP_PAYLOAD_SIZE));
+
+    let job_tx = worker_pool::spawn(WORKER_POOL_SIZE, &socket, MAX_DNS_UDP_PACKET_SIZE as u16, EDNS_MAX_UDP_PAYLOAD_SIZE);
+
+    let mut receive_packet_buf = [0; EDNS_MAX_UDP_PAYLOAD_SIZE as usize];
+    println!("Awaiting incoming packets...");
+    loop {
+        let (byte_count_received, sender_addr) = socket.recv_from(&mut receive_packet_buf).expect("Failed to read from the socket");
+        println!("We've received a DNS query of {byte_count_received} bytes from {sender_addr:?}");
+
+        // Copy the datagram out of the shared receive buffer before handing
+        // it to a worker, so no two threads ever alias the same bytes.
+        let packet = receive_packet_buf[..byte_count_received].to_vec();
+        if job_tx.send((packet, sender_addr)).is_err() {
+            println!("Worker pool job queue is gone; dropping query from {sender_addr:?}");
+        }
+    }
+}
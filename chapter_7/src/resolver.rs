@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::OnceLock;
+
+use crate::cache;
+use crate::dns_message::{QueryType, Question, Record, RecordData};
+use crate::forwarder;
+
+const DEFAULT_TTL: u32 = 300;
+
+static UPSTREAM_RESOLVER: OnceLock<Option<SocketAddr>> = OnceLock::new();
+
+/// Enables forwarding mode: names we aren't authoritative for are looked up
+/// against `upstream` instead of returning nothing. Must be called before
+/// the first call to `resolve`; later calls are ignored.
+pub fn set_upstream_resolver(upstream: Option<SocketAddr>) {
+    let _ = UPSTREAM_RESOLVER.set(upstream);
+}
+
+fn upstream_resolver() -> Option<SocketAddr> {
+    *UPSTREAM_RESOLVER.get_or_init(|| None)
+}
+
+/// Our (currently static) in-memory zone. A recursive or forwarding resolver
+/// would plug in here instead of, or in addition to, this table.
+fn zone() -> &'static HashMap<(String, QueryType), RecordData> {
+    static ZONE: OnceLock<HashMap<(String, QueryType), RecordData>> = OnceLock::new();
+    ZONE.get_or_init(|| {
+        let mut zone = HashMap::new();
+        zone.insert(("penpal.test".to_string(), QueryType::A), RecordData::A(Ipv4Addr::new(127, 0, 0, 1)));
+        zone.insert(("penpal.test".to_string(), QueryType::Aaaa), RecordData::Aaaa(Ipv6Addr::LOCALHOST));
+        zone
+    })
+}
+
+/// Looks up answers for `question`: our static zone first, then the
+/// forwarding cache, then (if forwarding mode is enabled) an upstream
+/// resolver, whose answer gets cached for next time.
+pub fn resolve(question: &Question) -> Vec<Record> {
+    if let Some(data) = zone().get(&(question.qname.clone(), question.qtype)) {
+        return vec![Record {
+            name: question.qname.clone(),
+            rtype: question.qtype,
+            rclass: question.qclass,
+            ttl: DEFAULT_TTL,
+            data: data.clone(),
+        }];
+    }
+
+    if let Some(cached) = cache::get(&question.qname, question.qtype) {
+        return cached;
+    }
+
+    let Some(upstream) = upstream_resolver() else {
+        return Vec::new();
+    };
+
+    match forwarder::forward(upstream, question) {
+        Ok(records) => {
+            cache::put(&question.qname, question.qtype, records.clone());
+            records
+        }
+        Err(err) => {
+            println!("Forwarding {} failed: {err}", question.qname);
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::{dns_message, resolver, response};
+
+/// Accepts DNS-over-TCP connections and serves each on its own thread.
+/// RFC 1035 §4.2.2 prefixes every TCP message with a 2-byte big-endian
+/// length, which is how a client ends up here after receiving a truncated
+/// UDP response.
+pub fn serve(listener: TcpListener, our_max_udp_payload_size: u16) {
+    for incoming in listener.incoming() {
+        let stream = match incoming {
+            Ok(stream) => stream,
+            Err(err) => {
+                println!("Failed to accept a TCP connection: {err}");
+                continue;
+            }
+        };
+        std::thread::spawn(move || handle_connection(stream, our_max_udp_payload_size));
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, our_max_udp_payload_size: u16) {
+    loop {
+        let mut length_prefix = [0u8; 2];
+        if stream.read_exact(&mut length_prefix).is_err() {
+            return;
+        }
+        let message_len = u16::from_be_bytes(length_prefix) as usize;
+
+        let mut message_buf = vec![0u8; message_len];
+        if stream.read_exact(&mut message_buf).is_err() {
+            return;
+        }
+
+        let query = match dns_message::parse(&message_buf) {
+            Ok(query) => query,
+            Err(err) => {
+                println!("Failed to parse a TCP query: {err}");
+                return;
+            }
+        };
+
+        let answers: Vec<_> = query.questions.iter().flat_map(resolver::resolve).collect();
+        let response = response::build_response(&query, answers, our_max_udp_payload_size);
+        let response_buf = dns_message::serialize(&response);
+
+        let response_length_prefix = (response_buf.len() as u16).to_be_bytes();
+        if stream.write_all(&response_length_prefix).is_err() || stream.write_all(&response_buf).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{dns_message, resolver, response};
+
+/// A single datagram handed from the receive loop to a worker: the raw bytes
+/// (copied out of the shared receive buffer) and who sent it.
+pub type Job = (Vec<u8>, SocketAddr);
+
+/// Spawns `pool_size` worker threads, each owning a cloned sending handle on
+/// `socket`, and returns the channel the receive loop should hand incoming
+/// datagrams off to. `min_udp_payload_size`/`max_udp_payload_size` bound the
+/// EDNS(0)-negotiated response size, same as the single-threaded loop used.
+pub fn spawn(pool_size: usize, socket: &UdpSocket, min_udp_payload_size: u16, max_udp_payload_size: u16) -> Sender<Job> {
+    let (tx, rx) = mpsc::channel::<Job>();
+    let rx = Arc::new(Mutex::new(rx));
+
+    for worker_id in 0..pool_size {
+        let rx = Arc::clone(&rx);
+        let socket = socket.try_clone().expect("Failed to clone our UDP socket for a worker thread");
+        thread::spawn(move || worker_loop(worker_id, rx, socket, min_udp_payload_size, max_udp_payload_size));
+    }
+
+    tx
+}
+
+fn worker_loop(worker_id: usize, rx: Arc<Mutex<Receiver<Job>>>, socket: UdpSocket, min_udp_payload_size: u16, max_udp_payload_size: u16) {
+    loop {
+        let (packet, sender_addr) = match rx.lock().expect("Worker pool job queue poisoned").recv() {
+            Ok(job) => job,
+            Err(_) => return, // The sending half was dropped; nothing left to do.
+        };
+
+        handle_query(worker_id, &socket, &packet, sender_addr, min_udp_payload_size, max_udp_payload_size);
+    }
+}
+
+fn handle_query(worker_id: usize, socket: &UdpSocket, packet: &[u8], sender_addr: SocketAddr, min_udp_payload_size: u16, max_udp_payload_size: u16) {
+    let query = match dns_message::parse(packet) {
+        Ok(query) => query,
+        Err(err) => {
+            println!("[worker {worker_id}] Failed to parse query from {sender_addr:?}: {err}");
+            return;
+        }
+    };
+    println!("[worker {worker_id}] Header: {:?}", query.header);
+
+    let peer_udp_payload_size = query
+        .edns
+        .map(|edns| edns.requestor_payload_size.clamp(min_udp_payload_size, max_udp_payload_size))
+        .unwrap_or(min_udp_payload_size);
+
+    let answers: Vec<_> = query.questions.iter().flat_map(resolver::resolve).collect();
+    let response = response::build_response(&query, answers, max_udp_payload_size);
+
+    let mut response_buf = dns_message::serialize(&response);
+    if response_buf.len() > peer_udp_payload_size as usize {
+        response_buf = dns_message::serialize(&response::build_truncated(&query));
+    }
+
+    if let Err(err) = socket.send_to(&response_buf, sender_addr) {
+        println!("[worker {worker_id}] Failed to send response to {sender_addr:?}: {err}");
+    }
+}